@@ -8,6 +8,9 @@ pub const pointer = @import("pointer.zig");
 pub const max_size_list = @import("max_size_list.zig");
 pub const set_list = @import("set_list.zig");
 pub const Shtick = @import("shtick.zig").Shtick;
+pub const Shtick32 = @import("shtick.zig").Shtick32;
+pub const Shtick64 = @import("shtick.zig").Shtick64;
+pub const ShtickN = @import("shtick.zig").ShtickN;
 pub const testing = @import("testing.zig");
 pub const time = @import("time.zig");
 pub const wrap_list = @import("wrap_list.zig");
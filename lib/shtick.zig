@@ -1,428 +1,907 @@
 const common = @import("common.zig");
+const owned_list = @import("owned_list.zig");
 
 const std = @import("std");
 
-// TODO: make ShtickN(i16), (i32), (i64)
-// with corresponding local capacities of 14 bytes (16 bytes total local bytes)
-// 20 bytes (24 bytes total), and 24 bytes (32 bytes), respectively.
-pub const Shtick = extern struct {
-    pub const Error = error{
-        string_too_long,
-        out_of_memory,
+pub const Shtick16 = ShtickN(i16);
+pub const Shtick32 = ShtickN(i32);
+pub const Shtick64 = ShtickN(i64);
+pub const Shtick = Shtick16;
+
+/// A string whose count fits in the signed `Count` type, which keeps short strings
+/// off the heap.  `ShtickN(i16)`, `(i32)`, and `(i64)` have corresponding local
+/// capacities of 14 bytes (16 bytes total local bytes), 20 bytes (24 bytes total),
+/// and 24 bytes (32 bytes), respectively.
+pub fn ShtickN(comptime Count: type) type {
+    const Capacity = std.meta.Int(.unsigned, @bitSizeOf(Count));
+    // Has room to negate `std.math.minInt(Count)`.
+    const WideCount = std.meta.Int(.signed, @bitSizeOf(Count) + 1);
+    // Local bytes before `capacity`, so that `pointer` lands on an 8-byte boundary
+    // right after `capacity`.
+    const start_count = switch (Count) {
+        i16 => 4,
+        i32, i64 => 8,
+        else => @compileError("ShtickN needs an i16, i32, or i64 count"),
     };
-    pub const max_count: usize = -std.math.minInt(i16);
-    pub const max_unallocated_count: usize = @sizeOf(Self) - @sizeOf(i16);
-
-    // The sign (optional +1) indicates whether we're allocated (or not).
-    // If <= 0, then negate it for the actual count of the allocated Shtick.
-    // If > 0, subtract one to get the actual count of the unallocated Shtick.
-    special_count: i16 = 1,
-    start: [4]u8 = undefined,
-    middle: extern union {
-        capacity: u16,
-        if_unallocated: [2]u8,
-    } = undefined,
-    end: extern union {
-        pointer: *u8,
-        if_unallocated: [8]u8,
-    } = undefined,
-
-    pub fn count(self: *const Self) usize {
-        if (self.isAllocated()) {
-            return @intCast(-(self.special_count + 1) + 1);
-        } else {
-            return @intCast(self.special_count - 1);
+    return extern struct {
+        pub const Error = error{
+            string_too_long,
+            out_of_memory,
+            invalid_utf8,
+            out_of_bounds,
+        };
+        pub const max_count: usize = -std.math.minInt(Count);
+        pub const max_unallocated_count: usize = @sizeOf(Self) - @sizeOf(Count);
+
+        // The sign (optional +1) indicates whether we're allocated (or not).
+        // If <= 0, then negate it for the actual count of the allocated Shtick.
+        // If > 0, subtract one to get the actual count of the unallocated Shtick.
+        special_count: Count = 1,
+        start: [start_count]u8 = undefined,
+        middle: extern union {
+            capacity: Capacity,
+            if_unallocated: [@sizeOf(Capacity)]u8,
+        } = undefined,
+        end: extern union {
+            pointer: *u8,
+            if_unallocated: [8]u8,
+        } = undefined,
+
+        pub fn count(self: *const Self) usize {
+            if (self.isAllocated()) {
+                const wide_count: WideCount = self.special_count;
+                return @intCast(-wide_count);
+            } else {
+                return @intCast(self.special_count - 1);
+            }
         }
-    }
 
-    inline fn setUnallocatedCountUnchecked(self: *Self, new_count: usize) void {
-        std.debug.assert(new_count <= max_unallocated_count);
-        self.special_count = @intCast(new_count + 1);
-    }
+        inline fn setUnallocatedCountUnchecked(self: *Self, new_count: usize) void {
+            std.debug.assert(new_count <= max_unallocated_count);
+            self.special_count = @intCast(new_count + 1);
+        }
 
-    inline fn setAllocatedCountUnchecked(self: *Self, new_count: usize) void {
-        std.debug.assert(new_count <= max_count);
-        const count32: i32 = @intCast(new_count);
-        self.special_count = @intCast(-count32);
-    }
+        inline fn setAllocatedCountUnchecked(self: *Self, new_count: usize) void {
+            std.debug.assert(new_count <= max_count);
+            const wide_count: WideCount = @intCast(new_count);
+            self.special_count = @intCast(-wide_count);
+        }
 
-    inline fn setCountUnchecked(self: *Self, new_count: usize) void {
-        if (self.isAllocated()) {
-            self.setAllocatedCountUnchecked(new_count);
-        } else {
-            self.setUnallocatedCountUnchecked(new_count);
+        inline fn setCountUnchecked(self: *Self, new_count: usize) void {
+            if (self.isAllocated()) {
+                self.setAllocatedCountUnchecked(new_count);
+            } else {
+                self.setUnallocatedCountUnchecked(new_count);
+            }
         }
-    }
 
-    pub inline fn isAllocated(self: *const Self) bool {
-        return self.special_count <= 0;
-    }
+        pub inline fn isAllocated(self: *const Self) bool {
+            return self.special_count <= 0;
+        }
 
-    pub inline fn isUnallocated(self: *const Self) bool {
-        return self.special_count > 0;
-    }
+        pub inline fn isUnallocated(self: *const Self) bool {
+            return self.special_count > 0;
+        }
 
-    pub fn deinit(self: *Self) void {
-        if (self.isAllocated()) {
-            common.allocator.free(self.buffer());
+        pub fn deinit(self: *Self) void {
+            if (self.isAllocated()) {
+                common.allocator.free(self.buffer());
+            }
+            self.special_count = 1;
         }
-        self.special_count = 1;
-    }
 
-    pub fn moot(self: *Self) Self {
-        const new_shtick = self.*;
-        self.* = Self{};
-        return new_shtick;
-    }
+        pub fn moot(self: *Self) Self {
+            const new_shtick = self.*;
+            self.* = Self{};
+            return new_shtick;
+        }
 
-    pub fn init(chars: []const u8) !Self {
-        var shtick = try Self.withCapacity(chars.len);
-        @memcpy(shtick.buffer()[0..chars.len], chars);
-        shtick.setCountUnchecked(chars.len);
-        return shtick;
-    }
+        /// Gives up ownership of the bytes, returning them as a heap slice that is
+        /// intentionally never freed by this shtick (e.g., for globals or interning).
+        /// Allocated shticks hand over their heap buffer, shrunk to `count()` in place
+        /// if the allocator allows it (otherwise it's copied into an exact-size buffer).
+        /// Unallocated shticks get copied to the heap first.  Resets `self` to empty.
+        pub fn leak(self: *Self) ![]u8 {
+            const current_count = self.count();
+            if (current_count <= max_unallocated_count) {
+                const heap = common.allocator.dupe(u8, self.slice()) catch return Error.out_of_memory;
+                self.deinit();
+                return heap;
+            }
+            if (!common.allocator.resize(self.bufferAllocated(), current_count)) {
+                try self.setCapacity(current_count);
+            }
+            const heap = maxBuffer(self.end.pointer, current_count);
+            self.* = Self{};
+            return heap;
+        }
 
-    pub fn copyFrom(self: *Self, other: Self) !void {
-        try self.copyFromSlice(other.slice());
-    }
+        pub fn init(chars: []const u8) !Self {
+            var shtick = try Self.withCapacity(chars.len);
+            @memcpy(shtick.buffer()[0..chars.len], chars);
+            shtick.setCountUnchecked(chars.len);
+            return shtick;
+        }
 
-    pub fn copyFromSlice(self: *Self, chars: []const u8) !void {
-        if (self.capacity() >= chars.len) {
-            @memcpy(self.buffer()[0..chars.len], chars);
-            self.setCountUnchecked(chars.len);
-        } else {
-            const new_shtick = try Shtick.init(chars);
-            self.deinit();
-            self.* = new_shtick;
+        /// Returns an independent copy; `deinit` it separately from `self`.
+        /// Unallocated shticks are copied without touching the heap.
+        pub fn clone(self: *const Self) !Self {
+            if (self.isUnallocated()) {
+                return self.*;
+            }
+            return Self.init(self.slice());
         }
-    }
 
-    pub fn add(self: *Self, other: Self) !void {
-        try self.addSlice(other.slice());
-    }
+        /// Makes sure this shtick owns its bytes (locally or on the heap).  All shticks
+        /// currently do, so this is a no-op, but it's a hook for borrowed shticks later.
+        pub fn ensureOwned(self: *Self) !void {
+            _ = self;
+        }
 
-    pub fn addSlice(self: *Self, chars: []const u8) !void {
-        const current_count = self.count();
-        if (chars.len > max_count or current_count + chars.len > max_count) {
-            return Error.string_too_long;
+        /// Initializes a `Shtick` with all of the `parts` joined together, allocating once.
+        pub fn initConcat(parts: []const []const u8) !Self {
+            var total_count: usize = 0;
+            for (parts) |part| {
+                if (part.len > max_count or total_count + part.len > max_count) {
+                    return Error.string_too_long;
+                }
+                total_count += part.len;
+            }
+            var shtick = try Self.withCapacity(total_count);
+            var index: usize = 0;
+            for (parts) |part| {
+                @memcpy(shtick.buffer()[index .. index + part.len], part);
+                index += part.len;
+            }
+            shtick.setCountUnchecked(total_count);
+            return shtick;
         }
-        const new_count = current_count + chars.len;
-        if (self.capacity() < new_count) {
-            try self.setCapacity(new_count);
+
+        pub fn copyFrom(self: *Self, other: Self) !void {
+            try self.copyFromSlice(other.slice());
         }
-        @memcpy(self.buffer()[current_count..new_count], chars);
-        self.setCountUnchecked(new_count);
-    }
 
-    /// Initializes a `Shtick` that is just on the stack (no allocations on the heap).
-    /// For compile-time-known `chars` only.  For anything else, prefer `init` and
-    /// just defer `deinit` to be safe.  If you ever do `self.copyFrom` with the shtick
-    /// returned here, or any other capacity-modifying methods, you should defer `deinit`.
-    pub inline fn unallocated(chars: anytype) Self {
-        // We're expecting `chars` to be `*const [n:0]u8` with n <= max_unallocated_count
-        if (chars.len > max_unallocated_count) {
-            @compileError(std.fmt.comptimePrint("Shtick.unallocated must have {d} characters or less", .{max_unallocated_count}));
+        pub fn copyFromSlice(self: *Self, chars: []const u8) !void {
+            if (self.capacity() >= chars.len) {
+                @memcpy(self.buffer()[0..chars.len], chars);
+                self.setCountUnchecked(chars.len);
+            } else {
+                const new_shtick = try Self.init(chars);
+                self.deinit();
+                self.* = new_shtick;
+            }
         }
-        return Self.init(chars) catch unreachable;
-    }
 
-    pub inline fn capacity(self: *const Self) usize {
-        if (self.isAllocated()) {
-            return self.capacityAllocated();
+        pub fn add(self: *Self, other: Self) !void {
+            try self.addSlice(other.slice());
         }
-        return max_unallocated_count;
-    }
 
-    /// Unchecked as to whether we're really allocated.
-    fn capacityAllocated(self: *const Self) usize {
-        return self.middle.capacity;
-    }
+        pub fn addSlice(self: *Self, chars: []const u8) !void {
+            const current_count = self.count();
+            if (chars.len > max_count or current_count + chars.len > max_count) {
+                return Error.string_too_long;
+            }
+            const new_count = current_count + chars.len;
+            if (self.capacity() < new_count) {
+                try self.setCapacity(new_count);
+            }
+            @memcpy(self.buffer()[current_count..new_count], chars);
+            self.setCountUnchecked(new_count);
+        }
 
-    pub fn setCapacity(self: *Self, new_capacity: usize) !void {
-        const old_count = self.count();
-        const new_count = @min(old_count, new_capacity);
-        if (new_capacity <= max_unallocated_count) {
-            // Should make this Shtick unallocated.
-            if (self.isAllocated()) {
-                var old_buffer = self.buffer();
-                defer common.allocator.free(old_buffer);
-                @memcpy(self.bufferUnallocated()[0..new_count], old_buffer[0..new_count]);
-            }
-            self.setUnallocatedCountUnchecked(new_count);
-        } else {
-            // This Shtick will need to be allocated.
-            const old_capacity = self.capacityAllocated();
-            if (new_capacity == old_capacity) {
-                return;
+        /// Inserts `chars` at the byte `offset`, which can be `count()` to add to the end.
+        /// Works on bytes, so doesn't check that `offset` is at a unicode character boundary.
+        pub fn insertSlice(self: *Self, offset: usize, chars: []const u8) !void {
+            const current_count = self.count();
+            if (offset > current_count) {
+                return Error.out_of_bounds;
+            }
+            if (chars.len > max_count or current_count + chars.len > max_count) {
+                return Error.string_too_long;
+            }
+            const new_count = current_count + chars.len;
+            if (self.capacity() < new_count) {
+                try self.setCapacity(new_count);
+            }
+            const full_buffer = self.buffer();
+            std.mem.copyBackwards(u8, full_buffer[offset + chars.len .. new_count], full_buffer[offset..current_count]);
+            @memcpy(full_buffer[offset .. offset + chars.len], chars);
+            self.setCountUnchecked(new_count);
+        }
+
+        /// Copies `chars` over the bytes starting at `offset`, without changing the count.
+        pub fn overwrite(self: *Self, offset: usize, chars: []const u8) !void {
+            const current_count = self.count();
+            if (offset > current_count or chars.len > current_count - offset) {
+                return Error.out_of_bounds;
+            }
+            @memcpy(self.buffer()[offset .. offset + chars.len], chars);
+        }
+
+        /// Adds `times` copies of `char` to the end, with at most one allocation.
+        pub fn addRepeated(self: *Self, char: u8, times: usize) !void {
+            const current_count = self.count();
+            if (times > max_count or current_count + times > max_count) {
+                return Error.string_too_long;
+            }
+            const new_count = current_count + times;
+            if (self.capacity() < new_count) {
+                try self.setCapacity(new_count);
+            }
+            @memset(self.buffer()[current_count..new_count], char);
+            self.setCountUnchecked(new_count);
+        }
+
+        /// Initializes a `Shtick` that is just on the stack (no allocations on the heap).
+        /// For compile-time-known `chars` only.  For anything else, prefer `init` and
+        /// just defer `deinit` to be safe.  If you ever do `self.copyFrom` with the shtick
+        /// returned here, or any other capacity-modifying methods, you should defer `deinit`.
+        pub inline fn unallocated(chars: anytype) Self {
+            // We're expecting `chars` to be `*const [n:0]u8` with n <= max_unallocated_count
+            if (chars.len > max_unallocated_count) {
+                @compileError(std.fmt.comptimePrint("Shtick.unallocated must have {d} characters or less", .{max_unallocated_count}));
             }
-            // Avoid destroying invariants: allocate first in case we have problems.
-            // This will throw if new_capacity > max_count.
-            const new_pointer = try allocate(new_capacity);
+            return Self.init(chars) catch unreachable;
+        }
+
+        pub inline fn capacity(self: *const Self) usize {
             if (self.isAllocated()) {
-                var old_buffer = self.bufferAllocated();
-                defer common.allocator.free(old_buffer);
-                @memcpy(maxBuffer(new_pointer, new_capacity)[0..new_count], old_buffer[0..new_count]);
+                return self.capacityAllocated();
+            }
+            return max_unallocated_count;
+        }
+
+        /// Unchecked as to whether we're really allocated.
+        fn capacityAllocated(self: *const Self) usize {
+            return self.middle.capacity;
+        }
+
+        pub fn setCapacity(self: *Self, new_capacity: usize) !void {
+            const old_count = self.count();
+            const new_count = @min(old_count, new_capacity);
+            if (new_capacity <= max_unallocated_count) {
+                // Should make this Shtick unallocated.
+                if (self.isAllocated()) {
+                    var old_buffer = self.buffer();
+                    defer common.allocator.free(old_buffer);
+                    @memcpy(self.bufferUnallocated()[0..new_count], old_buffer[0..new_count]);
+                }
+                self.setUnallocatedCountUnchecked(new_count);
             } else {
-                var old_buffer = self.bufferUnallocated();
-                @memcpy(maxBuffer(new_pointer, new_capacity)[0..new_count], old_buffer[0..new_count]);
+                // This Shtick will need to be allocated.
+                // When unallocated, `middle` holds characters, not a capacity.
+                if (self.isAllocated() and new_capacity == self.capacityAllocated()) {
+                    return;
+                }
+                // Avoid destroying invariants: allocate first in case we have problems.
+                // This will throw if new_capacity > max_count.
+                const new_pointer = try allocate(new_capacity);
+                if (self.isAllocated()) {
+                    var old_buffer = self.bufferAllocated();
+                    defer common.allocator.free(old_buffer);
+                    @memcpy(maxBuffer(new_pointer, new_capacity)[0..new_count], old_buffer[0..new_count]);
+                } else {
+                    var old_buffer = self.bufferUnallocated();
+                    @memcpy(maxBuffer(new_pointer, new_capacity)[0..new_count], old_buffer[0..new_count]);
+                }
+                self.middle.capacity = @intCast(new_capacity);
+                self.end.pointer = new_pointer;
+                self.setAllocatedCountUnchecked(new_count);
             }
-            self.middle.capacity = @intCast(new_capacity);
-            self.end.pointer = new_pointer;
-            self.setAllocatedCountUnchecked(new_count);
         }
-    }
 
-    pub fn withCapacity(starting_capacity: anytype) !Self {
-        if (starting_capacity <= max_unallocated_count) {
-            return .{};
+        /// Whether a shtick with `char_count` characters would need the heap.
+        pub inline fn willAllocate(char_count: usize) bool {
+            return char_count > max_unallocated_count;
         }
-        var shtick = Self{ .special_count = 0 };
-        const pointer = try allocate(starting_capacity);
-        shtick.middle.capacity = @intCast(starting_capacity);
-        shtick.end.pointer = @ptrCast(pointer);
-        return shtick;
-    }
 
-    fn allocate(starting_capacity: usize) !*u8 {
-        if (starting_capacity > max_count) {
-            return Error.string_too_long;
+        /// Changes the count, growing capacity if needed.  Any new bytes are zeroed.
+        pub fn setCount(self: *Self, new_count: usize) !void {
+            const old_count = self.count();
+            if (new_count > old_count) {
+                if (new_count > max_count) {
+                    return Error.string_too_long;
+                }
+                if (self.capacity() < new_count) {
+                    try self.setCapacity(new_count);
+                }
+                @memset(self.buffer()[old_count..new_count], 0);
+            }
+            self.setCountUnchecked(new_count);
         }
-        const heap = common.allocator.alloc(u8, starting_capacity) catch {
-            std.debug.print("couldn't allocate {d}-character Shtick...\n", .{starting_capacity});
-            return Error.out_of_memory;
-        };
-        return @ptrCast(heap.ptr);
-    }
 
-    /// Only use at start of shtick creation.
-    inline fn buffer(self: *Self) []u8 {
-        if (self.isUnallocated()) {
-            return self.bufferUnallocated();
-        } else {
-            return self.bufferAllocated();
+        /// Makes sure there's room for `additional` more bytes, allocating exactly
+        /// that much (i.e., without extra space) if needed.
+        pub fn reserve(self: *Self, additional: usize) !void {
+            const current_count = self.count();
+            if (additional > max_count or current_count + additional > max_count) {
+                return Error.string_too_long;
+            }
+            const needed_capacity = current_count + additional;
+            if (self.capacity() < needed_capacity) {
+                try self.setCapacity(needed_capacity);
+            }
         }
-    }
 
-    /// Doesn't do any checks.
-    fn bufferUnallocated(self: *Self) []u8 {
-        const current_capacity = max_unallocated_count;
-        const full_small_buffer: *[current_capacity]u8 = @ptrCast(&self.start[0]);
-        return full_small_buffer[0..current_capacity];
-    }
+        /// Reduces capacity down to `count()`, which can make this shtick unallocated.
+        pub fn shrinkToFit(self: *Self) !void {
+            if (self.isUnallocated()) {
+                return;
+            }
+            try self.setCapacity(self.count());
+        }
 
-    fn bufferAllocated(self: *Self) []u8 {
-        return maxBuffer(self.end.pointer, self.middle.capacity);
-    }
+        pub fn withCapacity(starting_capacity: anytype) !Self {
+            if (starting_capacity <= max_unallocated_count) {
+                return .{};
+            }
+            var shtick = Self{ .special_count = 0 };
+            const pointer = try allocate(starting_capacity);
+            shtick.middle.capacity = @intCast(starting_capacity);
+            shtick.end.pointer = @ptrCast(pointer);
+            return shtick;
+        }
 
-    fn maxBuffer(pointer: *u8, max_capacity: usize) []u8 {
-        const full_buffer: *[Self.max_count]u8 = @ptrCast(pointer);
-        return full_buffer[0..max_capacity];
-    }
+        fn allocate(starting_capacity: usize) !*u8 {
+            if (starting_capacity > max_count) {
+                return Error.string_too_long;
+            }
+            const heap = common.allocator.alloc(u8, starting_capacity) catch {
+                std.debug.print("couldn't allocate {d}-character Shtick...\n", .{starting_capacity});
+                return Error.out_of_memory;
+            };
+            return @ptrCast(heap.ptr);
+        }
+
+        /// Only use at start of shtick creation.
+        inline fn buffer(self: *Self) []u8 {
+            if (self.isUnallocated()) {
+                return self.bufferUnallocated();
+            } else {
+                return self.bufferAllocated();
+            }
+        }
 
-    pub fn slice(self: *const Self) []const u8 {
-        if (self.isUnallocated()) {
+        /// Doesn't do any checks.
+        fn bufferUnallocated(self: *Self) []u8 {
             const current_capacity = max_unallocated_count;
-            const full_small_buffer: *const [current_capacity]u8 = @ptrCast(&self.start[0]);
-            return full_small_buffer[0..self.count()];
-        } else {
-            const full_buffer: *const [Self.max_count]u8 = @ptrCast(self.end.pointer);
-            return full_buffer[0..self.count()];
+            const full_small_buffer: *[current_capacity]u8 = @ptrCast(&self.start[0]);
+            return full_small_buffer[0..current_capacity];
+        }
+
+        fn bufferAllocated(self: *Self) []u8 {
+            return maxBuffer(self.end.pointer, self.middle.capacity);
         }
-    }
 
-    pub const PascalCase = enum {
-        keep_starting_case, // lower_case -> lowerCase, Upper_case -> UpperCase
-        start_lower, // Upper_case -> upperCase, lower_case -> lowerCase
-        start_upper, // Upper_case -> UpperCase, lower_case -> LowerCase
+        fn maxBuffer(pointer: *u8, max_capacity: usize) []u8 {
+            const full_buffer: [*]u8 = @ptrCast(pointer);
+            return full_buffer[0..max_capacity];
+        }
 
-        fn transform(self: PascalCase, char: u8, at_start: bool) u8 {
-            if (!at_start) return char;
+        pub fn slice(self: *const Self) []const u8 {
+            if (self.isUnallocated()) {
+                const current_capacity = max_unallocated_count;
+                const full_small_buffer: *const [current_capacity]u8 = @ptrCast(&self.start[0]);
+                return full_small_buffer[0..self.count()];
+            } else {
+                const full_buffer: [*]const u8 = @ptrCast(self.end.pointer);
+                return full_buffer[0..self.count()];
+            }
+        }
 
-            return switch (self) {
-                .keep_starting_case => char,
-                .start_lower => uncapitalize(char),
-                .start_upper => capitalize(char),
-            };
+        /// Like `slice` but checks that the bytes are valid UTF-8.  Shticks hold
+        /// arbitrary bytes (e.g., via `init`), so use this if the contents must be text.
+        pub fn utf8Slice(self: *const Self) Error![]const u8 {
+            const chars = self.slice();
+            if (!std.unicode.utf8ValidateSlice(chars)) {
+                return Error.invalid_utf8;
+            }
+            return chars;
+        }
+
+        /// Iterates over unicode characters via `nextCodepoint()`; errors if the
+        /// bytes aren't valid UTF-8 (see `utf8Slice`).  For bytes, just use `slice()`.
+        pub fn charIterator(self: *const Self) Error!std.unicode.Utf8Iterator {
+            const view = std.unicode.Utf8View.init(self.slice()) catch return Error.invalid_utf8;
+            return view.iterator();
+        }
+
+        /// Decodes the unicode character starting at the byte `offset`.
+        /// Errors if `offset` is past the end or in the middle of a character.
+        pub fn charAt(self: *const Self, offset: usize) Error!u21 {
+            const chars = self.slice();
+            if (offset >= chars.len) {
+                return Error.out_of_bounds;
+            }
+            const sequence_count = std.unicode.utf8ByteSequenceLength(chars[offset]) catch return Error.invalid_utf8;
+            if (offset + sequence_count > chars.len) {
+                return Error.invalid_utf8;
+            }
+            return std.unicode.utf8Decode(chars[offset .. offset + sequence_count]) catch Error.invalid_utf8;
+        }
+
+        /// Decodes all the unicode characters into a list; make sure to `deinit` it.
+        pub fn charList(self: *const Self) !owned_list.OwnedList(u21) {
+            var list = owned_list.OwnedList(u21).init();
+            errdefer list.deinit();
+            var iterator = try self.charIterator();
+            while (iterator.nextCodepoint()) |char| {
+                try list.append(char);
+            }
+            return list;
         }
-    };
 
-    pub fn toPascalCase(self: *const Self, case: PascalCase) !Self {
-        if (self.count() == 0) {
-            return Self{};
-        }
-        var work_buffer: [32768]u8 = undefined;
-        var index: usize = 0;
-        var capitalize_next = false;
-        var at_start = true;
-        for (self.slice()) |char| if (char == '_') {
-            capitalize_next = true;
-        } else {
-            const modified_char = if (capitalize_next) capitalize(char) else char;
-            capitalize_next = false;
-            work_buffer[index] = case.transform(modified_char, at_start);
-            at_start = false;
-            index += 1;
+        /// Iterates over the byte offsets where unicode characters start,
+        /// including 0 and `count()` at the end.
+        pub fn charBoundaries(self: *const Self) CharBoundaryIterator {
+            return .{ .chars = self.slice() };
+        }
+
+        pub const CharBoundaryIterator = struct {
+            chars: []const u8,
+            offset: usize = 0,
+            done: bool = false,
+
+            pub fn next(self: *CharBoundaryIterator) ?usize {
+                if (self.done) return null;
+                const boundary = self.offset;
+                if (boundary >= self.chars.len) {
+                    self.done = true;
+                    return boundary;
+                }
+                const sequence_count = std.unicode.utf8ByteSequenceLength(self.chars[boundary]) catch 1;
+                self.offset = @min(boundary + sequence_count, self.chars.len);
+                return boundary;
+            }
+        };
+
+        pub const PascalCase = enum {
+            keep_starting_case, // lower_case -> lowerCase, Upper_case -> UpperCase
+            start_lower, // Upper_case -> upperCase, lower_case -> lowerCase
+            start_upper, // Upper_case -> UpperCase, lower_case -> LowerCase
+
+            fn transform(self: PascalCase, char: u8, at_start: bool) u8 {
+                if (!at_start) return char;
+
+                return switch (self) {
+                    .keep_starting_case => char,
+                    .start_lower => uncapitalize(char),
+                    .start_upper => capitalize(char),
+                };
+            }
         };
-        return Self.init(work_buffer[0..index]);
-    }
 
-    pub const SnakeCase = enum {
-        start_lower, // LowerSnakeCase -> lower_snake_case
-        start_upper, // initialUpperSnakeCase -> Initial_upper_snake_case
-        /// Like `keep_starting_case`, this case doesn't force the initial char to be
-        /// "capitalized" or "uncapitalized".
-        no_uppers, // Upper_case -> _upper_case
-        keep_starting_case, // keepLower -> keep_lower or KeepUpper -> Keep_upper, _prefix_ok -> _prefix_ok
-
-        // Since SnakeCase can add chars (e.g., myPascal -> my_pascal), return
-        // up to two chars in a u16, little-endian style.  (First char is (result & 255)
-        // and second char is (result >> 8).)
-        fn transform(self: SnakeCase, char: u8, at_start: bool, saw_underscore: bool) u16 {
-            return switch (self) {
-                .start_lower => transformStartLower(char, at_start),
-                .start_upper => transformStartUpper(char, at_start),
-                .no_uppers => transformNoUppers(char, at_start),
-                .keep_starting_case => transformKeepStartingCase(char, at_start, saw_underscore),
+        pub fn toPascalCase(self: *const Self, case: PascalCase) !Self {
+            if (self.count() == 0) {
+                return Self{};
+            }
+            // Underscores get dropped, so the result is never longer than `self`.
+            const result_count = self.count() - std.mem.count(u8, self.slice(), "_");
+            var result = try Self.withCapacity(result_count);
+            const result_buffer = result.buffer();
+            var index: usize = 0;
+            var capitalize_next = false;
+            var at_start = true;
+            for (self.slice()) |char| if (char == '_') {
+                capitalize_next = true;
+            } else {
+                const modified_char = if (capitalize_next) capitalize(char) else char;
+                capitalize_next = false;
+                result_buffer[index] = case.transform(modified_char, at_start);
+                at_start = false;
+                index += 1;
             };
+            result.setCountUnchecked(index);
+            return result;
         }
 
-        inline fn transformStartLower(char: u8, at_start: bool) u16 {
-            if (!isCapital(char)) {
-                return char;
-            } else if (at_start) {
-                return uncapitalize(char);
-            } else {
-                return underscoreChar(char);
+        pub const SnakeCase = enum {
+            start_lower, // LowerSnakeCase -> lower_snake_case
+            start_upper, // initialUpperSnakeCase -> Initial_upper_snake_case
+            /// Like `keep_starting_case`, this case doesn't force the initial char to be
+            /// "capitalized" or "uncapitalized".
+            no_uppers, // Upper_case -> _upper_case
+            keep_starting_case, // keepLower -> keep_lower or KeepUpper -> Keep_upper, _prefix_ok -> _prefix_ok
+
+            // Since SnakeCase can add chars (e.g., myPascal -> my_pascal), return
+            // up to two chars in a u16, little-endian style.  (First char is (result & 255)
+            // and second char is (result >> 8).)
+            fn transform(self: SnakeCase, char: u8, at_start: bool, saw_underscore: bool) u16 {
+                return switch (self) {
+                    .start_lower => transformStartLower(char, at_start),
+                    .start_upper => transformStartUpper(char, at_start),
+                    .no_uppers => transformNoUppers(char, at_start),
+                    .keep_starting_case => transformKeepStartingCase(char, at_start, saw_underscore),
+                };
+            }
+
+            inline fn transformStartLower(char: u8, at_start: bool) u16 {
+                if (!isCapital(char)) {
+                    return char;
+                } else if (at_start) {
+                    return uncapitalize(char);
+                } else {
+                    return underscoreChar(char);
+                }
+            }
+
+            inline fn transformStartUpper(char: u8, at_start: bool) u16 {
+                if (at_start) {
+                    return capitalize(char);
+                } else if (!isCapital(char)) {
+                    return char;
+                } else {
+                    return underscoreChar(char);
+                }
             }
+
+            inline fn transformNoUppers(char: u8, at_start: bool) u16 {
+                _ = at_start;
+                if (!isCapital(char)) {
+                    return char;
+                } else {
+                    return underscoreChar(char);
+                }
+            }
+
+            inline fn transformKeepStartingCase(char: u8, at_start: bool, saw_underscore: bool) u16 {
+                if ((at_start and !saw_underscore) or !isCapital(char)) {
+                    return char;
+                } else {
+                    return underscoreChar(char);
+                }
+            }
+
+            inline fn underscoreChar(char: u8) u16 {
+                const char16: u16 = uncapitalize(char);
+                return '_' | (char16 << 8);
+            }
+        };
+
+        pub fn toSnakeCase(self: *const Self, case: SnakeCase) !Self {
+            // Throws if the result would be longer than `max_count`.
+            var result = try Self.withCapacity(self.writeSnakeCase(case, null));
+            const result_count = self.writeSnakeCase(case, result.buffer());
+            result.setCountUnchecked(result_count);
+            return result;
         }
 
-        inline fn transformStartUpper(char: u8, at_start: bool) u16 {
-            if (at_start) {
-                return capitalize(char);
-            } else if (!isCapital(char)) {
-                return char;
-            } else {
-                return underscoreChar(char);
+        /// Returns the count of the snake-cased `self`, also writing it into
+        /// `maybe_buffer` if that's not null.
+        fn writeSnakeCase(self: *const Self, case: SnakeCase, maybe_buffer: ?[]u8) usize {
+            var index: usize = 0;
+            // If the shtick has an underscore before the next letter.
+            var underscore_next = false;
+            var at_start = true; // until we see a non-underscore character.
+            for (self.slice()) |char| {
+                if (char == '_') {
+                    underscore_next = true;
+                    continue;
+                }
+                // We'll pretend to capitalize after an underscore, but then transform
+                // it back as needed in `case.transform`.
+                const modified_char = if (underscore_next) capitalize(char) else char;
+                const sequence16 = case.transform(modified_char, at_start, underscore_next);
+                underscore_next = false;
+                at_start = false;
+                if (maybe_buffer) |buffer_to_write| {
+                    buffer_to_write[index] = @intCast(sequence16 & 255);
+                }
+                const next_char: u8 = @intCast(sequence16 >> 8);
+                index += 1;
+
+                if (next_char == 0) continue;
+
+                if (maybe_buffer) |buffer_to_write| {
+                    buffer_to_write[index] = uncapitalize(next_char);
+                }
+                index += 1;
             }
+            return index;
         }
 
-        inline fn transformNoUppers(char: u8, at_start: bool) u16 {
-            _ = at_start;
-            if (!isCapital(char)) {
-                return char;
-            } else {
-                return underscoreChar(char);
+        pub fn contains(self: Self, message: []const u8, where: common.At) bool {
+            const self_count = self.count();
+            if (self_count < message.len) {
+                return false;
             }
+            return switch (where) {
+                common.At.start => std.mem.eql(u8, self.slice()[0..message.len], message),
+                common.At.end => std.mem.eql(u8, self.slice()[self_count - message.len .. self_count], message),
+            };
         }
 
-        inline fn transformKeepStartingCase(char: u8, at_start: bool, saw_underscore: bool) u16 {
-            if ((at_start and !saw_underscore) or !isCapital(char)) {
-                return char;
-            } else {
-                return underscoreChar(char);
+        pub const Split = struct {
+            before: []const u8,
+            after: []const u8,
+        };
+
+        /// Splits around the first `delimiter`, or returns null if it's not present.
+        pub fn splitOnce(self: *const Self, delimiter: u8) ?Split {
+            const chars = self.slice();
+            const index = std.mem.indexOfScalar(u8, chars, delimiter) orelse return null;
+            return .{ .before = chars[0..index], .after = chars[index + 1 ..] };
+        }
+
+        /// Splits around the last `delimiter`, or returns null if it's not present.
+        pub fn rsplitOnce(self: *const Self, delimiter: u8) ?Split {
+            const chars = self.slice();
+            const index = std.mem.lastIndexOfScalar(u8, chars, delimiter) orelse return null;
+            return .{ .before = chars[0..index], .after = chars[index + 1 ..] };
+        }
+
+        /// Returns the slice without any leading or trailing bytes where
+        /// `predicate(context, byte)` is true; use `{}` for a stateless `context`.
+        pub fn trimMatches(self: *const Self, context: anytype, comptime predicate: fn (@TypeOf(context), u8) bool) []const u8 {
+            const chars = self.slice();
+            var start_index: usize = 0;
+            while (start_index < chars.len and predicate(context, chars[start_index])) {
+                start_index += 1;
             }
+            var end_index: usize = chars.len;
+            while (end_index > start_index and predicate(context, chars[end_index - 1])) {
+                end_index -= 1;
+            }
+            return chars[start_index..end_index];
         }
 
-        inline fn underscoreChar(char: u8) u16 {
-            const char16: u16 = uncapitalize(char);
-            return '_' | (char16 << 8);
+        /// Sets the count to zero but keeps the capacity.
+        pub fn clear(self: *Self) void {
+            self.setCountUnchecked(0);
         }
-    };
 
-    pub fn toSnakeCase(self: *const Self, case: SnakeCase) !Self {
-        var work_buffer: [32768]u8 = undefined;
-        var index: usize = 0;
-        // If the shtick has an underscore before the next letter.
-        var underscore_next = false;
-        var at_start = true; // until we see a non-underscore character.
-        for (self.slice()) |char| {
-            if (char == '_') {
-                underscore_next = true;
-                continue;
-            }
-            // We'll pretend to capitalize after an underscore, but then transform
-            // it back as needed in `case.transform`.
-            const modified_char = if (underscore_next) capitalize(char) else char;
-            const sequence16 = case.transform(modified_char, at_start, underscore_next);
-            underscore_next = false;
-            at_start = false;
-            if (index >= work_buffer.len) {
-                return Error.string_too_long;
+        /// Drops any bytes past `new_count`, keeping the capacity.  Does nothing
+        /// if `new_count` is at least `count()`.  Shouldn't split a UTF-8 character.
+        pub fn truncate(self: *Self, new_count: usize) void {
+            const chars = self.slice();
+            if (new_count >= chars.len) {
+                return;
             }
-            work_buffer[index] = @intCast(sequence16 & 255);
-            const next_char: u8 = @intCast(sequence16 >> 8);
-            index += 1;
+            // Continuation bytes look like 0b10xx_xxxx.
+            std.debug.assert(chars[new_count] & 0b1100_0000 != 0b1000_0000);
+            self.setCountUnchecked(new_count);
+        }
+
+        /// Iterates over `chunk_count`-byte slices; the last one can be shorter.
+        /// An empty shtick has no chunks.
+        pub fn byteChunks(self: *const Self, chunk_count: usize) ByteChunkIterator {
+            std.debug.assert(chunk_count > 0);
+            return .{ .chars = self.slice(), .chunk_count = chunk_count };
+        }
 
-            if (next_char == 0) continue;
+        pub const ByteChunkIterator = struct {
+            chars: []const u8,
+            chunk_count: usize,
+            offset: usize = 0,
 
-            if (index >= work_buffer.len) {
-                return Error.string_too_long;
+            pub fn next(self: *ByteChunkIterator) ?[]const u8 {
+                if (self.offset >= self.chars.len) return null;
+                const chunk_start = self.offset;
+                self.offset = chunk_start + @min(self.chars.len - chunk_start, self.chunk_count);
+                return self.chars[chunk_start..self.offset];
+            }
+        };
+
+        /// Keeps at most `max_chars` unicode characters (i.e., codepoints), cutting
+        /// on a character boundary.  Doesn't change capacity.
+        pub fn truncateChars(self: *Self, max_chars: usize) void {
+            const chars = self.slice();
+            var index: usize = 0;
+            var char_count: usize = 0;
+            while (index < chars.len and char_count < max_chars) {
+                const sequence_count = std.unicode.utf8ByteSequenceLength(chars[index]) catch 1;
+                index = @min(index + sequence_count, chars.len);
+                char_count += 1;
             }
-            work_buffer[index] = uncapitalize(next_char);
-            index += 1;
+            self.setCountUnchecked(index);
         }
-        return Self.init(work_buffer[0..index]);
-    }
 
-    pub fn contains(self: Self, message: []const u8, where: common.At) bool {
-        const self_count = self.count();
-        if (self_count < message.len) {
-            return false;
+        /// Returns the byte offset of the first `needle` in this shtick, if any.
+        pub fn find(self: *const Self, needle: []const u8) ?usize {
+            return std.mem.indexOf(u8, self.slice(), needle);
+        }
+
+        /// Returns the byte offset where the unicode character `char` first starts, if any.
+        pub fn findChar(self: *const Self, char: u21) ?usize {
+            var encoded: [4]u8 = undefined;
+            const encoded_count = std.unicode.utf8Encode(char, &encoded) catch return null;
+            return self.find(encoded[0..encoded_count]);
+        }
+
+        /// Whether `needle` is anywhere in this shtick; see `contains` for the start or end.
+        pub fn containsAnywhere(self: *const Self, needle: []const u8) bool {
+            return self.find(needle) != null;
+        }
+
+        /// Like `contains` but ignoring ASCII case, e.g., for file extensions.
+        pub fn containsIgnoringCase(self: Self, message: []const u8, where: common.At) bool {
+            const self_count = self.count();
+            if (self_count < message.len) {
+                return false;
+            }
+            return switch (where) {
+                common.At.start => std.ascii.eqlIgnoreCase(self.slice()[0..message.len], message),
+                common.At.end => std.ascii.eqlIgnoreCase(self.slice()[self_count - message.len .. self_count], message),
+            };
+        }
+
+        /// Keeps only the bytes where `predicate(context, byte)` is true, like `std.sort`
+        /// takes its `lessThan`.  This works on bytes, not unicode characters, so it
+        /// can break apart multi-byte characters.
+        pub fn retainBytes(self: *Self, context: anytype, comptime predicate: fn (@TypeOf(context), u8) bool) void {
+            const chars = self.buffer();
+            var kept_count: usize = 0;
+            for (0..self.count()) |index| {
+                const char = chars[index];
+                if (predicate(context, char)) {
+                    chars[kept_count] = char;
+                    kept_count += 1;
+                }
+            }
+            self.setCountUnchecked(kept_count);
+        }
+
+        /// Returns how many times each byte value occurs.
+        pub fn byteCounts(self: *const Self) [256]u32 {
+            var counts = [_]u32{0} ** 256;
+            for (self.slice()) |char| {
+                counts[char] += 1;
+            }
+            return counts;
+        }
+
+        pub inline fn printLine(self: *const Self, writer: anytype) !void {
+            try writer.print("{s}\n", .{self.slice()});
+        }
+
+        pub inline fn print(self: *const Self, writer: anytype) !void {
+            try writer.print("{s}", .{self.slice()});
+        }
+
+        pub fn equals(self: Self, other: anytype) bool {
+            const other_is_shtick = @TypeOf(other) == Self;
+            const other_count = if (other_is_shtick) other.count() else other.len;
+            if (other_count != self.count()) return false;
+            return std.mem.eql(u8, self.slice(), if (other_is_shtick) other.slice() else other);
+        }
+
+        /// Compares bytes lexicographically; `other` can be a `Shtick` or a slice.
+        pub fn order(self: Self, other: anytype) std.math.Order {
+            const other_is_shtick = @TypeOf(other) == Self;
+            return std.mem.order(u8, self.slice(), if (other_is_shtick) other.slice() else other);
+        }
+
+        /// For sorting, e.g., `std.mem.sort(Shtick, shticks, {}, Shtick.lessThan)`.
+        pub fn lessThan(_: void, a: Self, b: Self) bool {
+            return a.order(b) == .lt;
+        }
+
+        /// Supports `{}` in `std.fmt`, padding by unicode character count for
+        /// width and alignment, e.g., `{:>10}` or `{:*^9}`.
+        pub fn format(self: Self, comptime format_string: []const u8, options: std.fmt.FormatOptions, writer: anytype) !void {
+            _ = format_string;
+            try std.fmt.formatBuf(self.slice(), options, writer);
+        }
+
+        /// Hashes exactly like `std.hash_map.hashString(self.slice())`, so maps keyed
+        /// by `Shtick` can be looked up with a plain `[]const u8` (see `SliceHashContext`).
+        pub fn hash(self: *const Self) u64 {
+            return std.hash_map.hashString(self.slice());
         }
-        return switch (where) {
-            common.At.start => std.mem.eql(u8, self.slice()[0..message.len], message),
-            common.At.end => std.mem.eql(u8, self.slice()[self_count - message.len .. self_count], message),
+
+        /// Context for a `std.HashMap` keyed by `Shtick`.
+        pub const HashContext = struct {
+            pub fn hash(_: HashContext, shtick: Self) u64 {
+                return shtick.hash();
+            }
+
+            pub fn eql(_: HashContext, a: Self, b: Self) bool {
+                return a.equals(b);
+            }
         };
-    }
 
-    pub inline fn printLine(self: *const Self, writer: anytype) !void {
-        try writer.print("{s}\n", .{self.slice()});
-    }
+        /// Adapted context for looking up a `Shtick`-keyed map with a `[]const u8`,
+        /// e.g., `map.getAdapted(@as([]const u8, "key"), Shtick.SliceHashContext{})`.
+        pub const SliceHashContext = struct {
+            pub fn hash(_: SliceHashContext, chars: []const u8) u64 {
+                return std.hash_map.hashString(chars);
+            }
 
-    pub inline fn print(self: *const Self, writer: anytype) !void {
-        try writer.print("{s}", .{self.slice()});
-    }
+            pub fn eql(_: SliceHashContext, chars: []const u8, shtick: Self) bool {
+                return shtick.equals(chars);
+            }
+        };
 
-    pub fn equals(self: Self, other: anytype) bool {
-        const other_is_shtick = @TypeOf(other) == Self;
-        const other_count = if (other_is_shtick) other.count() else other.len;
-        if (other_count != self.count()) return false;
-        return std.mem.eql(u8, self.slice(), if (other_is_shtick) other.slice() else other);
-    }
+        pub fn expectEquals(a: Self, b: anytype) !void {
+            const b_is_shtick = @TypeOf(b) == Self;
+            const equal = a.equals(b);
+            if (!equal) {
+                std.debug.print("expected {s}, got {s}\n", .{ if (b_is_shtick) b.slice() else b, a.slice() });
+            }
+            try std.testing.expectEqual(true, equal);
+        }
 
-    pub fn expectEquals(a: Self, b: anytype) !void {
-        const b_is_shtick = @TypeOf(b) == Self;
-        const equal = a.equals(b);
-        if (!equal) {
-            std.debug.print("expected {s}, got {s}\n", .{ if (b_is_shtick) b.slice() else b, a.slice() });
+        /// Capitalizes the first letter of each whitespace-separated word and
+        /// uncapitalizes the rest, e.g., "hello WORLD" -> "Hello World".
+        /// Non-ASCII bytes are left alone.
+        pub fn toTitleCase(self: *const Self) !Self {
+            var shtick = try Self.init(self.slice());
+            var at_word_start = true;
+            for (shtick.buffer()[0..shtick.count()]) |*char| {
+                if (std.ascii.isWhitespace(char.*)) {
+                    at_word_start = true;
+                } else if (std.ascii.isAlphabetic(char.*)) {
+                    char.* = if (at_word_start) capitalize(char.*) else uncapitalize(char.*);
+                    at_word_start = false;
+                }
+            }
+            return shtick;
         }
-        try std.testing.expectEqual(true, equal);
-    }
 
-    pub inline fn isUncapital(char: u8) bool {
-        return char >= 'a' and char <= 'z';
-    }
+        /// Returns a copy that's safe to log: tabs, newlines, carriage returns,
+        /// backslashes, and quotes get a backslash, and any other byte outside of
+        /// printable ASCII becomes `\xNN`.
+        pub fn toEscaped(self: *const Self) !Self {
+            var escape_buffer: [4]u8 = undefined;
+            var escaped_count: usize = 0;
+            for (self.slice()) |char| {
+                escaped_count += escape(char, &escape_buffer).len;
+            }
+            if (escaped_count > max_count) {
+                return Error.string_too_long;
+            }
+            var shtick = try Self.withCapacity(escaped_count);
+            var index: usize = 0;
+            for (self.slice()) |char| {
+                const escaped = escape(char, &escape_buffer);
+                @memcpy(shtick.buffer()[index .. index + escaped.len], escaped);
+                index += escaped.len;
+            }
+            shtick.setCountUnchecked(escaped_count);
+            return shtick;
+        }
 
-    pub inline fn capitalize(char: u8) u8 {
-        return if (isUncapital(char))
-            char - 32
-        else
-            char;
-    }
+        fn escape(char: u8, escape_buffer: *[4]u8) []const u8 {
+            return switch (char) {
+                '\t' => "\\t",
+                '\r' => "\\r",
+                '\n' => "\\n",
+                '\\' => "\\\\",
+                '\'' => "\\'",
+                '"' => "\\\"",
+                0x20...0x21, 0x23...0x26, 0x28...0x5b, 0x5d...0x7e => blk: {
+                    escape_buffer[0] = char;
+                    break :blk escape_buffer[0..1];
+                },
+                else => std.fmt.bufPrint(escape_buffer, "\\x{x:0>2}", .{char}) catch unreachable,
+            };
+        }
 
-    pub inline fn isCapital(char: u8) bool {
-        return char >= 'A' and char <= 'Z';
-    }
+        pub inline fn isUncapital(char: u8) bool {
+            return char >= 'a' and char <= 'z';
+        }
 
-    pub inline fn uncapitalize(char: u8) u8 {
-        return if (isCapital(char))
-            char + 32
-        else
-            char;
-    }
+        pub inline fn capitalize(char: u8) u8 {
+            return if (isUncapital(char))
+                char - 32
+            else
+                char;
+        }
+
+        pub inline fn isCapital(char: u8) bool {
+            return char >= 'A' and char <= 'Z';
+        }
+
+        pub inline fn uncapitalize(char: u8) u8 {
+            return if (isCapital(char))
+                char + 32
+            else
+                char;
+        }
 
-    const Self = @This();
-};
+        const Self = @This();
+    };
+}
 
 test "Shtick size is correct" {
     try std.testing.expectEqual(16, @sizeOf(Shtick));
@@ -432,8 +911,71 @@ test "Shtick size is correct" {
     try std.testing.expectEqual(2, @sizeOf(@TypeOf(shtick.middle)));
     try std.testing.expectEqual(8, @sizeOf(@TypeOf(shtick.end)));
     try std.testing.expectEqual(14, Shtick.max_unallocated_count);
-    // TODO: check for `shtick.end.pointer` being at +8 from start of shtick
-    // try std.testing.expectEqual(8, @typeInfo(@TypeOf(shtick.end.pointer)).Pointer.alignment);
+    try std.testing.expectEqual(8, @offsetOf(Shtick, "end"));
+}
+
+test "Shtick32 size is correct" {
+    try std.testing.expectEqual(24, @sizeOf(Shtick32));
+    try std.testing.expectEqual(20, Shtick32.max_unallocated_count);
+    try std.testing.expectEqual(-std.math.minInt(i32), Shtick32.max_count);
+    try std.testing.expectEqual(12, @offsetOf(Shtick32, "middle"));
+    try std.testing.expectEqual(16, @offsetOf(Shtick32, "end"));
+}
+
+test "Shtick64 size is correct" {
+    try std.testing.expectEqual(32, @sizeOf(Shtick64));
+    try std.testing.expectEqual(24, Shtick64.max_unallocated_count);
+    try std.testing.expectEqual(-std.math.minInt(i64), Shtick64.max_count);
+    try std.testing.expectEqual(16, @offsetOf(Shtick64, "middle"));
+    try std.testing.expectEqual(24, @offsetOf(Shtick64, "end"));
+}
+
+test "Shtick32 unallocated and allocated" {
+    var shtick = Shtick32.unallocated("twenty characters ok");
+    defer shtick.deinit();
+    try std.testing.expectEqual(false, shtick.isAllocated());
+    try std.testing.expectEqual(20, shtick.count());
+
+    try shtick.addSlice("!");
+
+    try std.testing.expectEqual(true, shtick.isAllocated());
+    try std.testing.expectEqual(21, shtick.capacity());
+    try shtick.expectEquals("twenty characters ok!");
+}
+
+test "Shtick64 unallocated and allocated" {
+    var shtick = Shtick64.unallocated("twenty-four characters!!");
+    defer shtick.deinit();
+    try std.testing.expectEqual(false, shtick.isAllocated());
+    try std.testing.expectEqual(24, shtick.count());
+
+    try shtick.addSlice("?");
+
+    try std.testing.expectEqual(true, shtick.isAllocated());
+    try std.testing.expectEqual(25, shtick.capacity());
+    try shtick.expectEquals("twenty-four characters!!?");
+}
+
+test "Shtick32 case conversions past 32768 characters" {
+    var snake = try Shtick32.init("abc_d" ** 10_000);
+    defer snake.deinit();
+
+    var pascal = try snake.toPascalCase(.start_lower);
+    defer pascal.deinit();
+    try std.testing.expectEqual(40_000, pascal.capacity());
+    try pascal.expectEquals("abcD" ** 10_000);
+
+    var snake_again = try pascal.toSnakeCase(.start_lower);
+    defer snake_again.deinit();
+    try std.testing.expectEqual(50_000, snake_again.capacity());
+    try snake_again.expectEquals("abc_d" ** 10_000);
+}
+
+test "Shtick at max count" {
+    var shtick = try Shtick.init("g" ** Shtick.max_count);
+    defer shtick.deinit();
+
+    try std.testing.expectEqual(Shtick.max_count, shtick.count());
 }
 
 test "too large of a shtick" {
@@ -947,3 +1489,753 @@ test "equals works for large shticks" {
     const shtick4 = Shtick.unallocated("hello");
     try std.testing.expectEqual(false, shtick1.equals(shtick4));
 }
+
+test "truncateChars cuts on a character boundary" {
+    var shtick = try Shtick.init("aé€b and some more to be allocated");
+    defer shtick.deinit();
+
+    shtick.truncateChars(2);
+
+    const expected_count = (try std.unicode.utf8CodepointSequenceLength('a')) +
+        (try std.unicode.utf8CodepointSequenceLength('é'));
+    try std.testing.expectEqual(expected_count, shtick.count());
+    try shtick.expectEquals("aé");
+}
+
+test "truncateChars with more chars than present does nothing" {
+    var shtick = Shtick.unallocated("hi€");
+
+    shtick.truncateChars(10);
+
+    try shtick.expectEquals("hi€");
+}
+
+test "addRepeated appends copies of a char" {
+    var shtick = Shtick.unallocated("abc");
+    defer shtick.deinit();
+
+    try shtick.addRepeated('-', 10);
+
+    try std.testing.expectEqual(true, shtick.isUnallocated());
+    try std.testing.expectEqual(Shtick.max_unallocated_count, shtick.capacity());
+    try shtick.expectEquals("abc----------");
+
+    try shtick.addRepeated('=', 10);
+
+    try std.testing.expectEqual(true, shtick.isAllocated());
+    try std.testing.expectEqual(23, shtick.capacity());
+    try shtick.expectEquals("abc----------==========");
+}
+
+test "addRepeated reserves exactly once when already allocated" {
+    var shtick = try Shtick.init("this is allocated");
+    defer shtick.deinit();
+    try std.testing.expectEqual(17, shtick.capacity());
+
+    try shtick.addRepeated('!', 5);
+
+    try std.testing.expectEqual(true, shtick.isAllocated());
+    try std.testing.expectEqual(22, shtick.capacity());
+    try shtick.expectEquals("this is allocated!!!!!");
+}
+
+test "addRepeated too many" {
+    var shtick = Shtick.unallocated("a");
+    defer shtick.deinit();
+
+    try std.testing.expectError(Shtick.Error.string_too_long, shtick.addRepeated('-', Shtick.max_count));
+    try shtick.expectEquals("a");
+}
+
+test "Shtick-keyed hash map can be looked up with a slice" {
+    const Map = std.HashMap(Shtick, u32, Shtick.HashContext, std.hash_map.default_max_load_percentage);
+    var map = Map.init(common.allocator);
+    defer map.deinit();
+    var key = try Shtick.init("this key is long enough to be allocated");
+    defer key.deinit();
+
+    try map.put(key, 123);
+
+    try std.testing.expectEqual(std.hash_map.hashString(key.slice()), key.hash());
+    try std.testing.expectEqual(123, map.getAdapted(@as([]const u8, "this key is long enough to be allocated"), Shtick.SliceHashContext{}).?);
+    try std.testing.expectEqual(null, map.getAdapted(@as([]const u8, "this key is long"), Shtick.SliceHashContext{}));
+}
+
+test "splitOnce splits at the first delimiter" {
+    const shtick = Shtick.unallocated("a=b=c");
+
+    const split = shtick.splitOnce('=').?;
+
+    try std.testing.expectEqualStrings("a", split.before);
+    try std.testing.expectEqualStrings("b=c", split.after);
+}
+
+test "splitOnce without delimiter" {
+    try std.testing.expectEqual(null, Shtick.unallocated("abc").splitOnce('='));
+}
+
+test "rsplitOnce splits at the last delimiter" {
+    const shtick = Shtick.unallocated("a.b.c");
+
+    const split = shtick.rsplitOnce('.').?;
+
+    try std.testing.expectEqualStrings("a.b", split.before);
+    try std.testing.expectEqualStrings("c", split.after);
+    try std.testing.expectEqual(null, shtick.rsplitOnce('='));
+}
+
+test "containsIgnoringCase At.end" {
+    const shtick = Shtick.unallocated("Photo.JPG");
+
+    try std.testing.expect(shtick.containsIgnoringCase(".jpg", common.At.end));
+    try std.testing.expect(shtick.containsIgnoringCase(".JpG", common.At.end));
+    try std.testing.expect(!shtick.containsIgnoringCase(".png", common.At.end));
+    try std.testing.expect(!shtick.containsIgnoringCase("a photo.jpg", common.At.end));
+}
+
+test "containsIgnoringCase At.start" {
+    const shtick = Shtick.unallocated("Photo.JPG");
+
+    try std.testing.expect(shtick.containsIgnoringCase("PHOTO", common.At.start));
+    try std.testing.expect(!shtick.containsIgnoringCase("jpg", common.At.start));
+}
+
+test "willAllocate" {
+    try std.testing.expectEqual(false, Shtick.willAllocate(0));
+    try std.testing.expectEqual(false, Shtick.willAllocate(14));
+    try std.testing.expectEqual(true, Shtick.willAllocate(15));
+}
+
+test "retainBytes can remove null bytes" {
+    const Test = struct {
+        fn notNull(_: void, char: u8) bool {
+            return char != 0;
+        }
+    };
+    var shtick = try Shtick.init("\x00ab\x00\x00cdefghijklmnop\x00");
+    defer shtick.deinit();
+
+    shtick.retainBytes({}, Test.notNull);
+
+    try shtick.expectEquals("abcdefghijklmnop");
+}
+
+test "retainBytes with a stateful context" {
+    const Remover = struct {
+        removed: u8,
+        removed_count: usize = 0,
+
+        fn keep(self: *@This(), char: u8) bool {
+            if (char != self.removed) return true;
+            self.removed_count += 1;
+            return false;
+        }
+    };
+    var shtick = try Shtick.init("remove every e from these sentences");
+    defer shtick.deinit();
+    var remover = Remover{ .removed = 'e' };
+
+    shtick.retainBytes(&remover, Remover.keep);
+
+    try shtick.expectEquals("rmov vry  from ths sntncs");
+    try std.testing.expectEqual(9, remover.removed_count);
+}
+
+test "byteCounts" {
+    const shtick = Shtick.unallocated("mississippi");
+
+    const counts = shtick.byteCounts();
+
+    try std.testing.expectEqual(4, counts['s']);
+    try std.testing.expectEqual(4, counts['i']);
+    try std.testing.expectEqual(2, counts['p']);
+    try std.testing.expectEqual(1, counts['m']);
+    try std.testing.expectEqual(0, counts['z']);
+}
+
+test "clone empty shtick" {
+    const shtick: Shtick = .{};
+
+    var cloned = try shtick.clone();
+    defer cloned.deinit();
+
+    try std.testing.expectEqual(false, cloned.isAllocated());
+    try cloned.expectEquals("");
+}
+
+test "clone 14-character shtick is independent" {
+    var shtick = Shtick.unallocated("abcdefghijklmn");
+    defer shtick.deinit();
+
+    var cloned = try shtick.clone();
+    defer cloned.deinit();
+    try std.testing.expectEqual(false, cloned.isAllocated());
+    try cloned.expectEquals("abcdefghijklmn");
+
+    try shtick.addSlice("op");
+    cloned.buffer()[0] = 'A';
+
+    try shtick.expectEquals("abcdefghijklmnop");
+    try cloned.expectEquals("Abcdefghijklmn");
+}
+
+test "clone 32-character shtick is independent" {
+    var shtick = try Shtick.init("abcdefghijklmnopqrstuvwxyz123456");
+    defer shtick.deinit();
+
+    var cloned = try shtick.clone();
+    defer cloned.deinit();
+    try std.testing.expectEqual(true, cloned.isAllocated());
+    try cloned.expectEquals("abcdefghijklmnopqrstuvwxyz123456");
+
+    try shtick.addSlice("789");
+    cloned.buffer()[0] = 'A';
+
+    try shtick.expectEquals("abcdefghijklmnopqrstuvwxyz123456789");
+    try cloned.expectEquals("Abcdefghijklmnopqrstuvwxyz123456");
+}
+
+test "equals ignores capacity" {
+    const shtick1 = Shtick.unallocated("abc");
+    var shtick2 = try Shtick.withCapacity(50);
+    defer shtick2.deinit();
+    try shtick2.copyFromSlice("abc but long enough to need the allocation");
+    try shtick2.copyFromSlice("abc");
+
+    try std.testing.expectEqual(true, shtick2.isAllocated());
+    try std.testing.expectEqual(true, shtick1.equals(shtick2));
+    try std.testing.expectEqual(true, shtick2.equals(shtick1));
+    try std.testing.expectEqual(std.math.Order.eq, shtick1.order(shtick2));
+}
+
+test "order is lexicographic" {
+    const abc = Shtick.unallocated("abc");
+    var abcd = try Shtick.init("abcd and then some more letters");
+    defer abcd.deinit();
+
+    try std.testing.expectEqual(std.math.Order.lt, abc.order(abcd));
+    try std.testing.expectEqual(std.math.Order.gt, abcd.order(abc));
+    try std.testing.expectEqual(std.math.Order.gt, abc.order("abb"));
+    try std.testing.expectEqual(std.math.Order.lt, abc.order("b"));
+    try std.testing.expectEqual(std.math.Order.eq, abc.order("abc"));
+}
+
+test "shticks can be sorted" {
+    var shticks = [_]Shtick{
+        Shtick.unallocated("pear"),
+        Shtick.unallocated("apple"),
+        Shtick.unallocated("fig"),
+        Shtick.unallocated("applesauce"),
+    };
+
+    std.mem.sort(Shtick, &shticks, {}, Shtick.lessThan);
+
+    try shticks[0].expectEquals("apple");
+    try shticks[1].expectEquals("applesauce");
+    try shticks[2].expectEquals("fig");
+    try shticks[3].expectEquals("pear");
+}
+
+test "allocated and unallocated shticks with the same contents hash the same" {
+    const Map = std.HashMap(Shtick, u32, Shtick.HashContext, std.hash_map.default_max_load_percentage);
+    var map = Map.init(common.allocator);
+    defer map.deinit();
+    const unallocated_key = Shtick.unallocated("hello");
+    var allocated_key = try Shtick.withCapacity(50);
+    defer allocated_key.deinit();
+    try allocated_key.copyFromSlice("hello");
+    try std.testing.expectEqual(true, allocated_key.isAllocated());
+
+    try map.put(unallocated_key, 1);
+    try map.put(allocated_key, 2);
+
+    try std.testing.expectEqual(unallocated_key.hash(), allocated_key.hash());
+    try std.testing.expectEqual(1, map.count());
+    try std.testing.expectEqual(2, map.get(unallocated_key).?);
+}
+
+test "ensureOwned keeps contents" {
+    var short_shtick = Shtick.unallocated("short");
+    defer short_shtick.deinit();
+    var long_shtick = try Shtick.init("long enough to be allocated");
+    defer long_shtick.deinit();
+
+    try short_shtick.ensureOwned();
+    try short_shtick.ensureOwned();
+    try long_shtick.ensureOwned();
+    try long_shtick.ensureOwned();
+
+    try std.testing.expectEqual(false, short_shtick.isAllocated());
+    try short_shtick.expectEquals("short");
+    try std.testing.expectEqual(true, long_shtick.isAllocated());
+    try long_shtick.expectEquals("long enough to be allocated");
+}
+
+test "utf8Slice works for valid UTF-8" {
+    const shtick = Shtick.unallocated("héllo €");
+
+    try std.testing.expectEqualStrings("héllo €", try shtick.utf8Slice());
+}
+
+test "utf8Slice errors for invalid UTF-8" {
+    var shtick = try Shtick.init("ok\xff\xfe");
+    defer shtick.deinit();
+
+    try std.testing.expectError(Shtick.Error.invalid_utf8, shtick.utf8Slice());
+    try std.testing.expectEqual(4, shtick.slice().len);
+}
+
+test "trimMatches strips from both ends" {
+    const Test = struct {
+        fn isStar(_: void, char: u8) bool {
+            return char == '*';
+        }
+    };
+    const shtick = Shtick.unallocated("**hi*there**");
+    const all_stars = Shtick.unallocated("*****");
+
+    try std.testing.expectEqualStrings("hi*there", shtick.trimMatches({}, Test.isStar));
+    try std.testing.expectEqualStrings("", all_stars.trimMatches({}, Test.isStar));
+}
+
+test "trimMatches with a stateful context" {
+    const Trimmer = struct {
+        trimmed: []const u8,
+        checked_count: usize = 0,
+
+        fn matches(self: *@This(), char: u8) bool {
+            self.checked_count += 1;
+            return std.mem.indexOfScalar(u8, self.trimmed, char) != null;
+        }
+    };
+    const shtick = Shtick.unallocated("-+-keep-+-me+-");
+    var trimmer = Trimmer{ .trimmed = "-+" };
+
+    try std.testing.expectEqualStrings("keep-+-me", shtick.trimMatches(&trimmer, Trimmer.matches));
+    try std.testing.expectEqual(7, trimmer.checked_count);
+}
+
+test "clear keeps capacity for allocated shticks" {
+    var shtick = try Shtick.init("this is long enough to be allocated");
+    defer shtick.deinit();
+
+    shtick.clear();
+
+    try std.testing.expectEqual(true, shtick.isAllocated());
+    try std.testing.expectEqual(35, shtick.capacity());
+    try std.testing.expectEqual(0, shtick.count());
+    try shtick.expectEquals("");
+}
+
+test "clear for unallocated shticks" {
+    var shtick = Shtick.unallocated("short");
+
+    shtick.clear();
+
+    try std.testing.expectEqual(false, shtick.isAllocated());
+    try shtick.expectEquals("");
+}
+
+test "truncate works" {
+    var shtick = try Shtick.init("this is long enough to be allocated");
+    defer shtick.deinit();
+
+    shtick.truncate(100);
+    try shtick.expectEquals("this is long enough to be allocated");
+
+    shtick.truncate(4);
+    try std.testing.expectEqual(true, shtick.isAllocated());
+    try std.testing.expectEqual(35, shtick.capacity());
+    try shtick.expectEquals("this");
+}
+
+test "equals works for byte slices" {
+    var shtick = try Shtick.init("bytes \x00\x01\x02 and more bytes");
+    defer shtick.deinit();
+    const matching: []const u8 = &[_]u8{ 'b', 'y', 't', 'e', 's', ' ', 0, 1, 2 } ++ " and more bytes";
+    const mismatching: []const u8 = &[_]u8{ 'b', 'y', 't', 'e', 's', ' ', 0, 1, 3 } ++ " and more bytes";
+
+    try std.testing.expectEqual(true, shtick.equals(matching));
+    try std.testing.expectEqual(false, shtick.equals(mismatching));
+    try std.testing.expectEqual(false, shtick.equals(matching[0..9]));
+}
+
+test "charIterator iterates over mixed-width characters" {
+    var shtick = try Shtick.init("aé€😀 and ascii");
+    defer shtick.deinit();
+    var collected: [32]u21 = undefined;
+    var char_count: usize = 0;
+
+    var iterator = try shtick.charIterator();
+    while (iterator.nextCodepoint()) |char| {
+        collected[char_count] = char;
+        char_count += 1;
+    }
+
+    try common.expectEqualIndexables(&[_]u21{ 'a', 'é', '€', '😀', ' ', 'a', 'n', 'd', ' ', 'a', 's', 'c', 'i', 'i' }, collected[0..char_count]);
+}
+
+test "charIterator errors for invalid UTF-8" {
+    const shtick = try Shtick.init("a\xffb");
+
+    try std.testing.expectError(Shtick.Error.invalid_utf8, shtick.charIterator());
+}
+
+test "setCount zeroes new bytes" {
+    var shtick = Shtick.unallocated("short");
+    defer shtick.deinit();
+
+    try shtick.setCount(40);
+
+    try std.testing.expectEqual(true, shtick.isAllocated());
+    try std.testing.expectEqual(40, shtick.count());
+    try std.testing.expectEqualStrings("short", shtick.slice()[0..5]);
+    for (shtick.slice()[5..]) |char| {
+        try std.testing.expectEqual(0, char);
+    }
+
+    try shtick.setCount(3);
+    try std.testing.expectEqual(40, shtick.capacity());
+    try shtick.expectEquals("sho");
+
+    try shtick.setCount(6);
+    try shtick.expectEquals("sho\x00\x00\x00");
+}
+
+test "setCount too large" {
+    var shtick = Shtick.unallocated("short");
+
+    try std.testing.expectError(Shtick.Error.string_too_long, shtick.setCount(Shtick.max_count + 1));
+    try shtick.expectEquals("short");
+}
+
+test "reserve allocates exactly" {
+    var shtick = Shtick.unallocated("abc");
+    defer shtick.deinit();
+
+    try shtick.reserve(10);
+    try std.testing.expectEqual(false, shtick.isAllocated());
+
+    try shtick.reserve(30);
+    try std.testing.expectEqual(true, shtick.isAllocated());
+    try std.testing.expectEqual(33, shtick.capacity());
+    try shtick.expectEquals("abc");
+
+    try shtick.reserve(20);
+    try std.testing.expectEqual(33, shtick.capacity());
+
+    try std.testing.expectError(Shtick.Error.string_too_long, shtick.reserve(Shtick.max_count));
+}
+
+test "reserve allocates even if inline characters look like the capacity" {
+    // Characters 4 and 5 overlap `middle.capacity`, here spelling out 30.
+    var shtick = Shtick.unallocated("abcd\x1e\x00");
+    defer shtick.deinit();
+
+    try shtick.reserve(24);
+
+    try std.testing.expectEqual(true, shtick.isAllocated());
+    try std.testing.expectEqual(30, shtick.capacity());
+    try shtick.addSlice("x" ** 24);
+    try shtick.expectEquals("abcd\x1e\x00" ++ "x" ** 24);
+}
+
+test "shrinkToFit can return to unallocated" {
+    var shtick = try Shtick.withCapacity(32);
+    defer shtick.deinit();
+    try shtick.copyFromSlice("hello");
+    try std.testing.expectEqual(true, shtick.isAllocated());
+
+    try shtick.shrinkToFit();
+
+    try std.testing.expectEqual(true, shtick.isUnallocated());
+    try shtick.expectEquals("hello");
+}
+
+test "shrinkToFit for allocated contents" {
+    var shtick = try Shtick.withCapacity(100);
+    defer shtick.deinit();
+    try shtick.copyFromSlice("this needs to stay allocated");
+
+    try shtick.shrinkToFit();
+
+    try std.testing.expectEqual(true, shtick.isAllocated());
+    try std.testing.expectEqual(28, shtick.capacity());
+    try shtick.expectEquals("this needs to stay allocated");
+}
+
+test "charBoundaries for mixed-width characters" {
+    const shtick = Shtick.unallocated("aé€😀b");
+    var boundaries: [8]usize = undefined;
+    var boundary_count: usize = 0;
+
+    var iterator = shtick.charBoundaries();
+    while (iterator.next()) |boundary| {
+        boundaries[boundary_count] = boundary;
+        boundary_count += 1;
+    }
+
+    try common.expectEqualIndexables(&[_]usize{ 0, 1, 3, 6, 10, 11 }, boundaries[0..boundary_count]);
+}
+
+test "charBoundaries for empty shtick" {
+    const shtick: Shtick = .{};
+
+    var iterator = shtick.charBoundaries();
+
+    try std.testing.expectEqual(0, iterator.next().?);
+    try std.testing.expectEqual(null, iterator.next());
+}
+
+test "find works" {
+    var shtick = try Shtick.init("needle in a haystack with a needle");
+    defer shtick.deinit();
+
+    try std.testing.expectEqual(0, shtick.find("needle").?);
+    try std.testing.expectEqual(12, shtick.find("haystack").?);
+    try std.testing.expectEqual(26, shtick.find("a needle").?);
+    try std.testing.expectEqual(0, shtick.find("").?);
+    try std.testing.expectEqual(null, shtick.find("pin"));
+    try std.testing.expectEqual(null, shtick.find("needle in a haystack with a needle!"));
+}
+
+test "containsAnywhere works" {
+    const shtick = Shtick.unallocated("haystack");
+
+    try std.testing.expect(shtick.containsAnywhere("hay"));
+    try std.testing.expect(shtick.containsAnywhere("yst"));
+    try std.testing.expect(shtick.containsAnywhere("stack"));
+    try std.testing.expect(shtick.containsAnywhere(""));
+    try std.testing.expect(!shtick.containsAnywhere("needle"));
+    try std.testing.expect(!shtick.containsAnywhere("haystacks"));
+}
+
+test "insertSlice in the middle" {
+    var shtick = Shtick.unallocated("\x00\x01\x05\x06");
+    defer shtick.deinit();
+
+    try shtick.insertSlice(2, "\x02\x03\x04");
+    try shtick.expectEquals("\x00\x01\x02\x03\x04\x05\x06");
+
+    try shtick.insertSlice(7, "\x07\x08\x09\x0a\x0b\x0c\x0d\x0e");
+    try std.testing.expectEqual(true, shtick.isAllocated());
+    try shtick.expectEquals("\x00\x01\x02\x03\x04\x05\x06\x07\x08\x09\x0a\x0b\x0c\x0d\x0e");
+
+    try shtick.insertSlice(0, "start ");
+    try shtick.expectEquals("start \x00\x01\x02\x03\x04\x05\x06\x07\x08\x09\x0a\x0b\x0c\x0d\x0e");
+}
+
+test "insertSlice out of bounds" {
+    var shtick = Shtick.unallocated("abc");
+
+    try std.testing.expectError(Shtick.Error.out_of_bounds, shtick.insertSlice(4, "d"));
+    try shtick.expectEquals("abc");
+}
+
+test "contains with empty message at start or end" {
+    const short_shtick = Shtick.unallocated("short");
+    var long_shtick = try Shtick.init("long enough to be allocated");
+    defer long_shtick.deinit();
+    const empty_shtick: Shtick = .{};
+
+    try std.testing.expect(short_shtick.contains("", common.At.start));
+    try std.testing.expect(short_shtick.contains("", common.At.end));
+    try std.testing.expect(long_shtick.contains("", common.At.start));
+    try std.testing.expect(long_shtick.contains("", common.At.end));
+    try std.testing.expect(empty_shtick.contains("", common.At.start));
+    try std.testing.expect(empty_shtick.contains("", common.At.end));
+    try std.testing.expect(!empty_shtick.contains("a", common.At.start));
+    try std.testing.expect(!empty_shtick.contains("a", common.At.end));
+}
+
+test "charAt for ASCII and multi-byte characters" {
+    const shtick = Shtick.unallocated("aé€b");
+
+    try std.testing.expectEqual('a', try shtick.charAt(0));
+    try std.testing.expectEqual('é', try shtick.charAt(1));
+    try std.testing.expectEqual('€', try shtick.charAt(3));
+    try std.testing.expectEqual('b', try shtick.charAt(6));
+}
+
+test "charAt errors" {
+    const shtick = Shtick.unallocated("aé€b");
+
+    try std.testing.expectError(Shtick.Error.invalid_utf8, shtick.charAt(2));
+    try std.testing.expectError(Shtick.Error.invalid_utf8, shtick.charAt(4));
+    try std.testing.expectError(Shtick.Error.out_of_bounds, shtick.charAt(7));
+}
+
+test "toTitleCase works" {
+    try (try Shtick.unallocated("hello WORLD").toTitleCase()).expectEquals("Hello World");
+    try (try Shtick.unallocated(" (aBC)\tdéf ").toTitleCase()).expectEquals(" (Abc)\tDéf ");
+    try (try Shtick.unallocated("").toTitleCase()).expectEquals("");
+
+    var shtick = try Shtick.init("this IS a longer tItLe, allocated");
+    defer shtick.deinit();
+    var title = try shtick.toTitleCase();
+    defer title.deinit();
+    try title.expectEquals("This Is A Longer Title, Allocated");
+}
+
+test "leak hands over an allocated shtick's bytes" {
+    var shtick = try Shtick.withCapacity(100);
+    defer shtick.deinit();
+    try shtick.copyFromSlice("this is going to be leaked");
+
+    const leaked = try shtick.leak();
+    // Only tests need to clean up.
+    defer common.allocator.free(leaked);
+
+    try std.testing.expectEqualStrings("this is going to be leaked", leaked);
+    try std.testing.expectEqual(true, shtick.isUnallocated());
+    try shtick.expectEquals("");
+}
+
+test "leak hands over a full allocated buffer without copying" {
+    var shtick = try Shtick.init("this is going to be leaked");
+    defer shtick.deinit();
+    try std.testing.expectEqual(shtick.count(), shtick.capacity());
+    const old_pointer = shtick.end.pointer;
+
+    const leaked = try shtick.leak();
+    defer common.allocator.free(leaked);
+
+    try std.testing.expectEqual(@intFromPtr(old_pointer), @intFromPtr(leaked.ptr));
+    try std.testing.expectEqualStrings("this is going to be leaked", leaked);
+    try shtick.expectEquals("");
+}
+
+test "leak copies an unallocated shtick's bytes" {
+    var shtick = Shtick.unallocated("short");
+
+    const leaked = try shtick.leak();
+    defer common.allocator.free(leaked);
+
+    try std.testing.expectEqualStrings("short", leaked);
+    try shtick.expectEquals("");
+}
+
+test "byteChunks works" {
+    const shtick = Shtick.unallocated("abcdefghij");
+
+    var iterator = shtick.byteChunks(4);
+
+    try std.testing.expectEqualStrings("abcd", iterator.next().?);
+    try std.testing.expectEqualStrings("efgh", iterator.next().?);
+    try std.testing.expectEqualStrings("ij", iterator.next().?);
+    try std.testing.expectEqual(null, iterator.next());
+}
+
+test "byteChunks has no chunks for an empty shtick" {
+    const shtick = Shtick{};
+
+    var iterator = shtick.byteChunks(4);
+
+    try std.testing.expectEqual(null, iterator.next());
+}
+
+test "byteChunks with a huge chunk count" {
+    const shtick = Shtick.unallocated("abcdefghij");
+
+    var iterator = shtick.byteChunks(std.math.maxInt(usize));
+
+    try std.testing.expectEqualStrings("abcdefghij", iterator.next().?);
+    try std.testing.expectEqual(null, iterator.next());
+}
+
+test "equals works for large shticks that differ at the end" {
+    var shtick1 = try Shtick.init("abcdefgh" ** 512);
+    defer shtick1.deinit();
+    var shtick2 = try Shtick.init("abcdefgh" ** 512);
+    defer shtick2.deinit();
+    var shtick3 = try Shtick.init("abcdefgh" ** 511 ++ "abcdefgH");
+    defer shtick3.deinit();
+
+    try std.testing.expectEqual(true, shtick1.equals(shtick2));
+    try std.testing.expectEqual(false, shtick1.equals(shtick3));
+}
+
+test "initConcat joins parts" {
+    var shtick = try Shtick.initConcat(&[_][]const u8{ "foo", "bar", "baz" });
+    defer shtick.deinit();
+    try shtick.expectEquals("foobarbaz");
+
+    var long_shtick = try Shtick.initConcat(&[_][]const u8{ "this is ", "", "long enough ", "to be allocated" });
+    defer long_shtick.deinit();
+    try std.testing.expectEqual(true, long_shtick.isAllocated());
+    try std.testing.expectEqual(35, long_shtick.capacity());
+    try long_shtick.expectEquals("this is long enough to be allocated");
+}
+
+test "initConcat too long" {
+    const half = "g" ** (Shtick.max_count / 2);
+    try std.testing.expectError(Shtick.Error.string_too_long, Shtick.initConcat(&[_][]const u8{ half, half, "g" }));
+}
+
+test "overwrite keeps the count" {
+    var shtick = try Shtick.init("overwrite the middle of this");
+    defer shtick.deinit();
+
+    try shtick.overwrite(10, "THE MIDDLE");
+    try shtick.expectEquals("overwrite THE MIDDLE of this");
+
+    try shtick.overwrite(24, "THIS");
+    try shtick.expectEquals("overwrite THE MIDDLE of THIS");
+}
+
+test "overwrite out of bounds" {
+    var shtick = Shtick.unallocated("short");
+
+    try std.testing.expectError(Shtick.Error.out_of_bounds, shtick.overwrite(3, "abc"));
+    try std.testing.expectError(Shtick.Error.out_of_bounds, shtick.overwrite(6, ""));
+    try shtick.expectEquals("short");
+}
+
+test "format pads to width" {
+    const shtick = Shtick.unallocated("héllo");
+    var buffer: [32]u8 = undefined;
+
+    try std.testing.expectEqualStrings("héllo", try std.fmt.bufPrint(&buffer, "{}", .{shtick}));
+    try std.testing.expectEqualStrings("     héllo", try std.fmt.bufPrint(&buffer, "{:>10}", .{shtick}));
+    try std.testing.expectEqualStrings("héllo     ", try std.fmt.bufPrint(&buffer, "{:<10}", .{shtick}));
+    try std.testing.expectEqualStrings("**héllo**", try std.fmt.bufPrint(&buffer, "{:*^9}", .{shtick}));
+    try std.testing.expectEqualStrings("héllo", try std.fmt.bufPrint(&buffer, "{:>3}", .{shtick}));
+}
+
+test "findChar works" {
+    const shtick = Shtick.unallocated("aé€b€");
+
+    try std.testing.expectEqual(0, shtick.findChar('a').?);
+    try std.testing.expectEqual(1, shtick.findChar('é').?);
+    try std.testing.expectEqual(3, shtick.findChar('€').?);
+    try std.testing.expectEqual(6, shtick.findChar('b').?);
+    try std.testing.expectEqual(null, shtick.findChar('😀'));
+    try std.testing.expectEqual(null, shtick.findChar('e'));
+}
+
+test "charList decodes mixed-width characters" {
+    var shtick = try Shtick.init("aé€😀 and more");
+    defer shtick.deinit();
+
+    var list = try shtick.charList();
+    defer list.deinit();
+
+    try std.testing.expectEqual(13, list.count());
+    try list.expectEquals(&[_]u21{ 'a', 'é', '€', '😀', ' ', 'a', 'n', 'd', ' ', 'm', 'o', 'r', 'e' });
+}
+
+test "toEscaped works" {
+    const shtick = Shtick.unallocated("a\nb\xff\"\\'\x00");
+
+    var escaped = try shtick.toEscaped();
+    defer escaped.deinit();
+
+    try escaped.expectEquals("a\\nb\\xff\\\"\\\\\\'\\x00");
+}
+
+test "toEscaped too long" {
+    var shtick = try Shtick.init("\xff" ** (Shtick.max_count / 4 + 1));
+    defer shtick.deinit();
+
+    try std.testing.expectError(Shtick.Error.string_too_long, shtick.toEscaped());
+}